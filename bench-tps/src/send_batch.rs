@@ -1,10 +1,21 @@
 use {
+    bincode::serialize,
     crate::bench_tps_client::*,
     log::*,
+    quinn::{ClientConfig, Endpoint, TransportConfig},
+    rand::{distributions::Alphanumeric, Rng, SeedableRng},
+    rand_chacha::ChaChaRng,
     rayon::prelude::*,
+    rcgen::generate_simple_self_signed,
+    rustls::{
+        client::{ServerCertVerified, ServerCertVerifier},
+        Certificate, PrivateKey,
+    },
+    solana_client::rpc_client::RpcClient,
     solana_core::gen_keys::GenKeys,
     solana_measure::measure::Measure,
     solana_sdk::{
+        clock::{Slot, NUM_CONSECUTIVE_LEADER_SLOTS},
         commitment_config::CommitmentConfig,
         hash::Hash,
         message::Message,
@@ -14,16 +25,20 @@ use {
         system_instruction,
         transaction::Transaction,
     },
+    spl_memo::build_memo,
     std::{
-        collections::HashSet,
+        collections::{HashMap, HashSet},
         marker::Send,
+        net::SocketAddr,
         sync::{
-            atomic::{AtomicBool, AtomicUsize, Ordering},
+            atomic::{AtomicU64, AtomicUsize, Ordering},
             Arc, Mutex,
         },
+        str::FromStr,
         thread::sleep,
         time::{Duration, Instant},
     },
+    tokio::runtime::Runtime,
 };
 
 pub fn get_latest_blockhash<T: BenchTpsClient>(client: &T) -> Hash {
@@ -38,6 +53,21 @@ pub fn get_latest_blockhash<T: BenchTpsClient>(client: &T) -> Hash {
     }
 }
 
+/// Like [`get_latest_blockhash`], but also returns the blockhash's last
+/// valid block height. Retries on error instead of propagating it, so a
+/// transient RPC hiccup doesn't take down a long-running replay loop.
+fn get_latest_blockhash_and_height<T: BenchTpsClient>(client: &T) -> (Hash, u64) {
+    loop {
+        match client.get_latest_blockhash_with_commitment(CommitmentConfig::processed()) {
+            Ok(result) => return result,
+            Err(err) => {
+                info!("Couldn't get last blockhash: {:?}", err);
+                sleep(Duration::from_secs(1));
+            }
+        };
+    }
+}
+
 pub fn generate_keypairs(seed_keypair: &Keypair, count: u64) -> (Vec<Keypair>, u64) {
     let mut seed = [0u8; 32];
     seed.copy_from_slice(&seed_keypair.to_bytes()[..32]);
@@ -54,6 +84,33 @@ pub fn generate_keypairs(seed_keypair: &Keypair, count: u64) -> (Vec<Keypair>, u
     (rnd.gen_n_keypairs(total_keys), extra)
 }
 
+/// Configures an optional fixed-size random payload attached to each
+/// benchmark transaction as a memo-program instruction, so transaction size
+/// and account-lock patterns can be varied to model realistic load. Payload
+/// bytes come from a `rand_chacha`-seeded RNG, the same approach
+/// [`generate_keypairs`] uses for keypairs, so a given seed always produces
+/// the same payloads and runs stay reproducible.
+#[derive(Clone, Debug, Default)]
+pub struct PayloadConfig {
+    /// Whether to attach a memo payload to each transaction at all.
+    pub include_memo: bool,
+    /// Size in bytes of the random payload to attach.
+    pub size_bytes: usize,
+    /// Seed for the ChaCha RNG generating payload bytes.
+    pub seed: u64,
+}
+
+/// Deterministically derives the memo payload for the `index`th transaction
+/// in a batch: same `payload_config.seed` and `index` always produce the
+/// same bytes. Drawn from an alphanumeric alphabet rather than raw random
+/// bytes, since the memo program requires its input to be valid UTF-8.
+fn generate_payload(payload_config: &PayloadConfig, index: u64) -> Vec<u8> {
+    let rng = ChaChaRng::seed_from_u64(payload_config.seed.wrapping_add(index));
+    rng.sample_iter(&Alphanumeric)
+        .take(payload_config.size_bytes)
+        .collect()
+}
+
 /// fund the dests keys by spending all of the source keys into MAX_SPENDS_PER_TX
 /// on every iteration.  This allows us to replay the transfers because the source is either empty,
 /// or full
@@ -64,6 +121,10 @@ pub fn fund_keys<T: 'static + BenchTpsClient + Send + Sync>(
     total: u64,
     max_fee: u64,
     lamports_per_account: u64,
+    metrics: Option<&VerifyMetrics>,
+    replay_config: &ReplayConfig,
+    tpu_sender: Option<&QuicTpuSender>,
+    payload_config: &PayloadConfig,
 ) {
     let mut funded: Vec<&Keypair> = vec![source];
     let mut funded_funds = total;
@@ -86,6 +147,10 @@ pub fn fund_keys<T: 'static + BenchTpsClient + Send + Sync>(
                 &client,
                 chunk,
                 to_lamports,
+                metrics,
+                replay_config,
+                tpu_sender,
+                payload_config,
             );
         });
 
@@ -102,6 +167,127 @@ const MAX_SPENDS_PER_TX: u64 = 4;
 // assume 4MB network buffers, and 512 byte packets
 const FUND_CHUNK_LEN: usize = 4 * 1024 * 1024 / 512;
 
+// Number of exponential buckets in `LatencyHistogram`. Bucket `i` counts
+// samples with `floor(log2(latency_micros)) == i`, so 64 buckets comfortably
+// covers any latency representable as a u64 microsecond count.
+const HISTOGRAM_BUCKETS: usize = 64;
+
+/// Lock-free exponential-bucket histogram of transaction confirmation
+/// latencies, so recording stays wait-free from the rayon workers in the
+/// verification pass of `FundingTransactions::fund`. Each bucket is an independent `AtomicU64`, incremented with
+/// `Relaxed` ordering since buckets don't need to be read consistently with
+/// one another between increments.
+struct LatencyHistogram {
+    buckets: Vec<AtomicU64>,
+}
+
+impl LatencyHistogram {
+    fn new() -> Self {
+        Self {
+            buckets: (0..HISTOGRAM_BUCKETS).map(|_| AtomicU64::new(0)).collect(),
+        }
+    }
+
+    fn record(&self, latency_micros: u64) {
+        let bucket = if latency_micros == 0 {
+            0
+        } else {
+            63 - latency_micros.leading_zeros() as usize
+        };
+        self.buckets[bucket.min(HISTOGRAM_BUCKETS - 1)].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Walks cumulative bucket counts until crossing the target rank for
+    /// percentile `p` (e.g. `0.99` for p99), and returns that bucket's lower
+    /// bound in microseconds as a representative latency.
+    fn percentile(&self, p: f64) -> u64 {
+        let counts: Vec<u64> = self
+            .buckets
+            .iter()
+            .map(|bucket| bucket.load(Ordering::Relaxed))
+            .collect();
+        let total: u64 = counts.iter().sum();
+        if total == 0 {
+            return 0;
+        }
+        let target_rank = ((total as f64) * p).ceil() as u64;
+        let mut cumulative = 0u64;
+        for (i, count) in counts.iter().enumerate() {
+            cumulative += count;
+            if cumulative >= target_rank {
+                return 1u64 << i;
+            }
+        }
+        1u64 << (HISTOGRAM_BUCKETS - 1)
+    }
+}
+
+/// A point-in-time snapshot of verify-loop throughput and confirmation
+/// latency, suitable for printing or emitting on a reporting interval.
+#[derive(Clone, Debug)]
+pub struct BenchStats {
+    pub verified_count: usize,
+    pub tps: f64,
+    pub p50_latency_us: u64,
+    pub p90_latency_us: u64,
+    pub p99_latency_us: u64,
+}
+
+/// Tracks per-transaction confirmation latency (time from when a transfer is
+/// first handed to the replay loop, just before its first send, to the
+/// moment `verify_funding_transfer` first returns true) and running TPS,
+/// across however many `FundingTransactions::fund` calls share it.
+pub struct VerifyMetrics {
+    histogram: LatencyHistogram,
+    verified_total: AtomicUsize,
+    last_report: Mutex<(Instant, usize)>,
+}
+
+impl VerifyMetrics {
+    pub fn new() -> Self {
+        Self {
+            histogram: LatencyHistogram::new(),
+            verified_total: AtomicUsize::new(0),
+            last_report: Mutex::new((Instant::now(), 0)),
+        }
+    }
+
+    fn record_verified(&self, latency: Duration) {
+        self.histogram.record(latency.as_micros() as u64);
+        self.verified_total.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Computes TPS as the verified-count delta since the previous snapshot
+    /// over the wall-clock delta, alongside the latency percentiles observed
+    /// so far.
+    pub fn snapshot(&self) -> BenchStats {
+        let verified_count = self.verified_total.load(Ordering::Relaxed);
+        let mut last_report = self.last_report.lock().unwrap();
+        let (last_time, last_count) = *last_report;
+        let elapsed = last_time.elapsed().as_secs_f64();
+        let tps = if elapsed > 0.0 {
+            verified_count.saturating_sub(last_count) as f64 / elapsed
+        } else {
+            0.0
+        };
+        *last_report = (Instant::now(), verified_count);
+
+        BenchStats {
+            verified_count,
+            tps,
+            p50_latency_us: self.histogram.percentile(0.50),
+            p90_latency_us: self.histogram.percentile(0.90),
+            p99_latency_us: self.histogram.percentile(0.99),
+        }
+    }
+}
+
+impl Default for VerifyMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 fn verify_funding_transfer<T: BenchTpsClient>(
     client: &Arc<T>,
     tx: &Transaction,
@@ -116,14 +302,294 @@ fn verify_funding_transfer<T: BenchTpsClient>(
     false
 }
 
+/// Resolves the TPU QUIC socket address of the leader scheduled for a given
+/// slot, and of the leaders scheduled after it. Backed by the cluster's
+/// leader schedule plus a gossip-derived slot -> leader -> TPU address map.
+pub trait LeaderTpuCache: Send + Sync {
+    /// Returns the TPU socket addresses for the leader at `current_slot` and
+    /// the `fanout` leaders scheduled after it, in order, deduplicated.
+    fn leader_tpu_sockets(&self, current_slot: Slot, fanout: usize) -> Vec<SocketAddr>;
+}
+
+/// [`LeaderTpuCache`] backed directly by cluster JSON-RPC calls: the leader
+/// schedule comes from `get_slot_leaders`, and leader pubkeys are resolved to
+/// TPU QUIC addresses via the gossip-derived node list from
+/// `get_cluster_nodes`. Both are re-fetched on every call, which is
+/// acceptable at `bench-tps`'s send cadence but would be too chatty for a
+/// production sender.
+pub struct RpcLeaderTpuCache {
+    rpc_client: Arc<RpcClient>,
+}
+
+impl RpcLeaderTpuCache {
+    pub fn new(rpc_client: Arc<RpcClient>) -> Self {
+        Self { rpc_client }
+    }
+}
+
+impl LeaderTpuCache for RpcLeaderTpuCache {
+    fn leader_tpu_sockets(&self, current_slot: Slot, fanout: usize) -> Vec<SocketAddr> {
+        // Each leader holds several consecutive slots, so fetch enough slots
+        // to span the current leader's window plus `fanout` more.
+        let leaders = match self.rpc_client.get_slot_leaders(
+            current_slot,
+            (fanout as u64 + 1) * NUM_CONSECUTIVE_LEADER_SLOTS,
+        ) {
+            Ok(leaders) => leaders,
+            Err(err) => {
+                warn!("failed to fetch slot leaders for slot {}: {}", current_slot, err);
+                return vec![];
+            }
+        };
+
+        let nodes = match self.rpc_client.get_cluster_nodes() {
+            Ok(nodes) => nodes,
+            Err(err) => {
+                warn!("failed to fetch cluster nodes: {}", err);
+                return vec![];
+            }
+        };
+        let tpu_quic_by_pubkey: HashMap<Pubkey, SocketAddr> = nodes
+            .into_iter()
+            .filter_map(|node| {
+                let pubkey = Pubkey::from_str(&node.pubkey).ok()?;
+                Some((pubkey, node.tpu_quic?))
+            })
+            .collect();
+
+        let mut seen = HashSet::new();
+        leaders
+            .into_iter()
+            .filter_map(|leader| tpu_quic_by_pubkey.get(&leader).copied())
+            .filter(|addr| seen.insert(*addr))
+            .take(fanout + 1)
+            .collect()
+    }
+}
+
+/// Tunables for the direct-to-TPU QUIC send path used by [`QuicTpuSender`].
+#[derive(Clone, Debug)]
+pub struct TpuSendConfig {
+    /// Number of upcoming leaders (beyond the current one) to duplicate each
+    /// transaction to.
+    pub fanout: usize,
+    /// QUIC connection establishment timeout.
+    pub connection_timeout: Duration,
+    /// Maximum number of concurrent unidirectional streams the *peer* may
+    /// open back to this client on a connection. `send_to` only ever opens
+    /// outbound streams, which this doesn't bound, so it's left at solana's
+    /// own QUIC client default of 0 to avoid accepting streams back from the
+    /// validator.
+    pub max_concurrent_uni_streams: u32,
+    /// Interval at which QUIC keepalive frames are sent on idle connections.
+    pub keep_alive_interval: Duration,
+}
+
+impl Default for TpuSendConfig {
+    fn default() -> Self {
+        Self {
+            fanout: 2,
+            connection_timeout: Duration::from_secs(2),
+            max_concurrent_uni_streams: 0,
+            keep_alive_interval: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Tunables for [`FundingTransactions::fund`]'s leader-aware replay loop.
+#[derive(Clone, Debug)]
+pub struct ReplayConfig {
+    /// Maximum number of resend attempts before a transaction is dropped.
+    pub max_retries: u32,
+    /// Delay before the first retry; doubles on each subsequent retry.
+    pub base_delay: Duration,
+    /// Upper bound the exponential backoff delay is capped at.
+    pub max_delay: Duration,
+    /// Keep retrying past the blockhash's last valid block height (re-signing with a
+    /// fresh blockhash) instead of dropping the transaction once it expires.
+    pub retry_after_blockhash_expiry: bool,
+}
+
+impl Default for ReplayConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 16,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(8),
+            retry_after_blockhash_expiry: true,
+        }
+    }
+}
+
+/// Accepts any server certificate presented on the TPU QUIC port. Validators
+/// identify themselves with a self-signed certificate rather than one issued
+/// by a CA, so the usual chain-of-trust verification would reject every
+/// leader; this is the same trust model solana's own QUIC client uses.
+struct SkipServerVerification;
+
+impl SkipServerVerification {
+    fn new() -> Arc<Self> {
+        Arc::new(Self)
+    }
+}
+
+impl ServerCertVerifier for SkipServerVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &Certificate,
+        _intermediates: &[Certificate],
+        _server_name: &rustls::ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: std::time::SystemTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+}
+
+/// Generates a throwaway self-signed certificate/key pair to present as this
+/// client's TLS identity. The TPU QUIC listener doesn't check client
+/// identity, so a fresh one can be minted per process.
+fn new_self_signed_tls_client_certificate() -> (Certificate, PrivateKey) {
+    let cert = generate_simple_self_signed(vec!["solana-tpu-client".to_string()])
+        .expect("failed to generate self-signed TPU client certificate");
+    let key = PrivateKey(cert.serialize_private_key_der());
+    let cert = Certificate(
+        cert.serialize_der()
+            .expect("failed to serialize TPU client certificate"),
+    );
+    (cert, key)
+}
+
+/// A small pool of reusable QUIC connections to leader TPUs, keyed by socket
+/// address so repeated sends to the same leader reuse the handshake instead
+/// of paying it on every transaction.
+struct QuicConnectionPool {
+    endpoint: Endpoint,
+    connections: tokio::sync::Mutex<HashMap<SocketAddr, quinn::Connection>>,
+    config: TpuSendConfig,
+}
+
+impl QuicConnectionPool {
+    fn new(config: TpuSendConfig) -> Self {
+        let mut endpoint =
+            Endpoint::client("0.0.0.0:0".parse().unwrap()).expect("create QUIC endpoint");
+        endpoint.set_default_client_config(Self::client_config(&config));
+        Self {
+            endpoint,
+            connections: tokio::sync::Mutex::new(HashMap::new()),
+            config,
+        }
+    }
+
+    fn client_config(config: &TpuSendConfig) -> ClientConfig {
+        let mut transport = TransportConfig::default();
+        transport
+            .max_concurrent_uni_streams(config.max_concurrent_uni_streams.into())
+            .keep_alive_interval(Some(config.keep_alive_interval));
+
+        // Validators don't present a CA-issued certificate on their TPU QUIC
+        // port, so native-roots verification rejects every handshake. Mirror
+        // solana's own QUIC client: skip server verification and present a
+        // throwaway self-signed client identity instead.
+        let (client_cert, client_key) = new_self_signed_tls_client_certificate();
+        let mut crypto = rustls::ClientConfig::builder()
+            .with_safe_defaults()
+            .with_custom_certificate_verifier(SkipServerVerification::new())
+            .with_single_cert(vec![client_cert], client_key)
+            .expect("failed to build TPU QUIC client TLS config");
+        crypto.enable_early_data = true;
+
+        let mut client_config = ClientConfig::new(Arc::new(crypto));
+        client_config.transport_config(Arc::new(transport));
+        client_config
+    }
+
+    async fn get_or_connect(&self, addr: SocketAddr) -> Result<quinn::Connection, String> {
+        let mut connections = self.connections.lock().await;
+        if let Some(conn) = connections.get(&addr) {
+            if conn.close_reason().is_none() {
+                return Ok(conn.clone());
+            }
+        }
+        let connecting = self
+            .endpoint
+            .connect(addr, "solana-tpu")
+            .map_err(|err| format!("failed to start QUIC connection to {}: {}", addr, err))?;
+        let conn = tokio::time::timeout(self.config.connection_timeout, connecting)
+            .await
+            .map_err(|_| format!("QUIC connection to {} timed out", addr))?
+            .map_err(|err| format!("QUIC connection to {} failed: {}", addr, err))?;
+        connections.insert(addr, conn.clone());
+        Ok(conn)
+    }
+
+    async fn send_to(&self, addr: SocketAddr, data: &[u8]) -> Result<(), String> {
+        let conn = self.get_or_connect(addr).await?;
+        let mut send_stream = conn
+            .open_uni()
+            .await
+            .map_err(|err| format!("failed to open uni stream to {}: {}", addr, err))?;
+        send_stream
+            .write_all(data)
+            .await
+            .map_err(|err| format!("failed to write to {}: {}", addr, err))?;
+        send_stream
+            .finish()
+            .await
+            .map_err(|err| format!("failed to finish stream to {}: {}", addr, err))?;
+        Ok(())
+    }
+}
+
+/// Sends transactions directly to the TPU QUIC ports of the current and next
+/// few leaders, bypassing the RPC `send_batch` hop entirely. This lets
+/// `bench-tps` exercise a validator's ingest path the same way production
+/// transaction senders do.
+pub struct QuicTpuSender {
+    leader_cache: Arc<dyn LeaderTpuCache>,
+    pool: QuicConnectionPool,
+    runtime: Runtime,
+}
+
+impl QuicTpuSender {
+    pub fn new(leader_cache: Arc<dyn LeaderTpuCache>, config: TpuSendConfig) -> Self {
+        Self {
+            pool: QuicConnectionPool::new(config),
+            leader_cache,
+            runtime: Runtime::new().expect("failed to create QUIC sender runtime"),
+        }
+    }
+
+    /// Convenience constructor for the common case: resolve leaders straight
+    /// off the cluster's own RPC node via [`RpcLeaderTpuCache`], rather than
+    /// requiring the caller to build a `LeaderTpuCache` themselves.
+    pub fn new_with_rpc_client(rpc_client: Arc<RpcClient>, config: TpuSendConfig) -> Self {
+        Self::new(Arc::new(RpcLeaderTpuCache::new(rpc_client)), config)
+    }
+
+    fn send_to_leaders(&self, current_slot: Slot, wire_txs: &[Vec<u8>]) {
+        let sockets = self
+            .leader_cache
+            .leader_tpu_sockets(current_slot, self.pool.config.fanout);
+        if sockets.is_empty() {
+            warn!("no leader TPU sockets available for slot {}", current_slot);
+            return;
+        }
+        self.runtime.block_on(async {
+            for wire_tx in wire_txs {
+                for &addr in &sockets {
+                    if let Err(err) = self.pool.send_to(addr, wire_tx).await {
+                        warn!("failed to send transaction to {}: {}", addr, err);
+                    }
+                }
+            }
+        });
+    }
+}
+
 trait SendBatchTransactions<'a, T: Sliceable + Send + Sync> {
     fn sign(&mut self, blockhash: Hash);
-    fn send<C: BenchTpsClient>(&self, client: &Arc<C>);
-    fn verify<C: 'static + BenchTpsClient + Send + Sync>(
-        &mut self,
-        client: &Arc<C>,
-        to_lamports: u64,
-    );
 }
 
 /// This trait allows reuse SendBatchTransactions to send
@@ -147,90 +613,6 @@ where
         sign_txs.stop();
         debug!("sign {} txs: {}us", self.len(), sign_txs.as_us());
     }
-
-    fn send<C: BenchTpsClient>(&self, client: &Arc<C>) {
-        let mut send_txs = Measure::start("send_and_clone_txs");
-        let batch: Vec<_> = self.iter().map(|(_keypair, tx)| tx.clone()).collect();
-        client.send_batch(batch).expect("transfer");
-        send_txs.stop();
-        debug!("send {} {}", self.len(), send_txs);
-    }
-
-    fn verify<C: 'static + BenchTpsClient + Send + Sync>(
-        &mut self,
-        client: &Arc<C>,
-        to_lamports: u64,
-    ) {
-        let starting_txs = self.len();
-        let verified_txs = Arc::new(AtomicUsize::new(0));
-        let too_many_failures = Arc::new(AtomicBool::new(false));
-        let loops = if starting_txs < 1000 { 3 } else { 1 };
-        // Only loop multiple times for small (quick) transaction batches
-        let time = Arc::new(Mutex::new(Instant::now()));
-        for _ in 0..loops {
-            let time = time.clone();
-            let failed_verify = Arc::new(AtomicUsize::new(0));
-            let client = client.clone();
-            let verified_txs = &verified_txs;
-            let failed_verify = &failed_verify;
-            let too_many_failures = &too_many_failures;
-            let verified_set: HashSet<Pubkey> = self
-                .par_iter()
-                .filter_map(move |(k, tx)| {
-                    let pubkey = k.get_pubkey();
-                    if too_many_failures.load(Ordering::Relaxed) {
-                        return None;
-                    }
-
-                    let verified = if verify_funding_transfer(&client, tx, to_lamports) {
-                        verified_txs.fetch_add(1, Ordering::Relaxed);
-                        Some(pubkey)
-                    } else {
-                        failed_verify.fetch_add(1, Ordering::Relaxed);
-                        None
-                    };
-
-                    let verified_txs = verified_txs.load(Ordering::Relaxed);
-                    let failed_verify = failed_verify.load(Ordering::Relaxed);
-                    let remaining_count = starting_txs.saturating_sub(verified_txs + failed_verify);
-                    if failed_verify > 100 && failed_verify > verified_txs {
-                        too_many_failures.store(true, Ordering::Relaxed);
-                        warn!(
-                            "Too many failed transfers... {} remaining, {} verified, {} failures",
-                            remaining_count, verified_txs, failed_verify
-                        );
-                    }
-                    if remaining_count > 0 {
-                        let mut time_l = time.lock().unwrap();
-                        if time_l.elapsed().as_secs() > 2 {
-                            info!(
-                                "Verifying transfers... {} remaining, {} verified, {} failures",
-                                remaining_count, verified_txs, failed_verify
-                            );
-                            *time_l = Instant::now();
-                        }
-                    }
-
-                    verified
-                })
-                .collect();
-
-            self.retain(|(k, _)| !verified_set.contains(&k.get_pubkey()));
-            if self.is_empty() {
-                break;
-            }
-            info!("Looping verifications");
-
-            let verified_txs = verified_txs.load(Ordering::Relaxed);
-            let failed_verify = failed_verify.load(Ordering::Relaxed);
-            let remaining_count = starting_txs.saturating_sub(verified_txs + failed_verify);
-            info!(
-                "Verifying transfers... {} remaining, {} verified, {} failures",
-                remaining_count, verified_txs, failed_verify
-            );
-            sleep(Duration::from_millis(100));
-        }
-    }
 }
 
 type FundingSigners<'a> = &'a Keypair;
@@ -247,14 +629,37 @@ impl<'a> Sliceable for FundingSigners<'a> {
     }
 }
 
+/// One in-flight transaction tracked by [`FundingTransactions::fund`]'s replay
+/// loop: its signer,
+/// last-signed wire form, and resend bookkeeping.
+struct ReplayEntry<'a> {
+    keypair: FundingSigners<'a>,
+    tx: Transaction,
+    retries: u32,
+    next_retry_at: Instant,
+    last_valid_block_height: u64,
+    /// When this transfer was first handed to the replayer. Anchors
+    /// confirmation latency independent of how many retries it takes or how
+    /// long the current replay tick has been running.
+    sent_at: Instant,
+}
+
+/// Interval at which the replayer wakes up to send due retries and check for
+/// newly confirmed transactions.
+const REPLAY_TICK: Duration = Duration::from_millis(200);
+
 trait FundingTransactions<'a>: SendBatchTransactions<'a, FundingSigners<'a>> {
     fn fund<T: 'static + BenchTpsClient + Send + Sync>(
         &mut self,
         client: &Arc<T>,
         to_fund: &FundingChunk<'a>,
         to_lamports: u64,
+        metrics: Option<&VerifyMetrics>,
+        replay_config: &ReplayConfig,
+        tpu_sender: Option<&QuicTpuSender>,
+        payload_config: &PayloadConfig,
     );
-    fn make(&mut self, to_fund: &FundingChunk<'a>);
+    fn make(&mut self, to_fund: &FundingChunk<'a>, payload_config: &PayloadConfig);
 }
 
 impl<'a> FundingTransactions<'a> for FundingContainer<'a> {
@@ -263,48 +668,149 @@ impl<'a> FundingTransactions<'a> for FundingContainer<'a> {
         client: &Arc<T>,
         to_fund: &FundingChunk<'a>,
         to_lamports: u64,
+        metrics: Option<&VerifyMetrics>,
+        replay_config: &ReplayConfig,
+        tpu_sender: Option<&QuicTpuSender>,
+        payload_config: &PayloadConfig,
     ) {
-        self.make(to_fund);
-
-        let mut tries = 0;
-        while !self.is_empty() {
-            info!(
-                "{} {} each to {} accounts in {} txs",
-                if tries == 0 {
-                    "transferring"
-                } else {
-                    " retrying"
-                },
-                to_lamports,
-                self.len() * MAX_SPENDS_PER_TX as usize,
-                self.len(),
-            );
+        self.make(to_fund, payload_config);
+
+        let (blockhash, last_valid_block_height) = get_latest_blockhash_and_height(client.as_ref());
+        self.sign(blockhash);
+
+        let mut entries: Vec<ReplayEntry<'a>> = self
+            .drain(..)
+            .map(|(keypair, tx)| ReplayEntry {
+                keypair,
+                tx,
+                retries: 0,
+                next_retry_at: Instant::now(),
+                last_valid_block_height,
+                sent_at: Instant::now(),
+            })
+            .collect();
+
+        info!(
+            "transferring {} each to {} accounts in {} txs",
+            to_lamports,
+            entries.len() * MAX_SPENDS_PER_TX as usize,
+            entries.len(),
+        );
+
+        while !entries.is_empty() {
+            let now = Instant::now();
+            let current_slot = client.get_slot().unwrap_or(0);
+            let current_block_height = client.get_block_height().unwrap_or(0);
+
+            // Drop anything that's exhausted its retries or, unless
+            // `retry_after_blockhash_expiry` is set, outlived its blockhash's
+            // last valid block height, rather than spinning on it forever.
+            // `retries` counts every send, the first one included.
+            entries.retain(|entry| {
+                if entry.retries > replay_config.max_retries {
+                    warn!(
+                        "dropping transaction after {} retries",
+                        entry.retries - 1
+                    );
+                    return false;
+                }
+                if !replay_config.retry_after_blockhash_expiry
+                    && current_block_height > entry.last_valid_block_height
+                {
+                    warn!("dropping transaction past its blockhash's last valid block height");
+                    return false;
+                }
+                true
+            });
+            if entries.is_empty() {
+                break;
+            }
+
+            // Re-sign any entries whose blockhash has gone stale so bounded
+            // retries still land on-chain.
+            if entries
+                .iter()
+                .any(|entry| current_block_height > entry.last_valid_block_height)
+            {
+                let (blockhash, last_valid_block_height) =
+                    get_latest_blockhash_and_height(client.as_ref());
+                for entry in entries.iter_mut() {
+                    entry.tx.sign(&[entry.keypair], blockhash);
+                    entry.last_valid_block_height = last_valid_block_height;
+                }
+            }
+
+            let ready: Vec<usize> = entries
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| entry.next_retry_at <= now)
+                .map(|(i, _)| i)
+                .collect();
 
-            let blockhash = get_latest_blockhash(client.as_ref());
+            if !ready.is_empty() {
+                let mut send_txs = Measure::start("replay_send_txs");
+                if let Some(tpu_sender) = tpu_sender {
+                    let wire_txs: Vec<Vec<u8>> = ready
+                        .iter()
+                        .map(|&i| serialize(&entries[i].tx).expect("serialize transaction"))
+                        .collect();
+                    tpu_sender.send_to_leaders(current_slot, &wire_txs);
+                } else {
+                    let batch: Vec<Transaction> =
+                        ready.iter().map(|&i| entries[i].tx.clone()).collect();
+                    client.send_batch(batch).expect("transfer");
+                }
+                send_txs.stop();
+                debug!("replay send {} txs: {}", ready.len(), send_txs);
 
-            // re-sign retained to_fund_txes with updated blockhash
-            self.sign(blockhash);
-            self.send(client);
+                for &i in &ready {
+                    let entry = &mut entries[i];
+                    entry.retries += 1;
+                    let backoff_exp = entry.retries.saturating_sub(1).min(20);
+                    let backoff = replay_config
+                        .base_delay
+                        .checked_mul(1u32 << backoff_exp)
+                        .unwrap_or(replay_config.max_delay)
+                        .min(replay_config.max_delay);
+                    entry.next_retry_at = Instant::now() + backoff;
+                }
+            }
 
-            // Sleep a few slots to allow transactions to process
-            sleep(Duration::from_secs(1));
+            sleep(REPLAY_TICK);
 
-            self.verify(client, to_lamports);
+            let verified: HashSet<Pubkey> = entries
+                .par_iter()
+                .filter_map(|entry| {
+                    if verify_funding_transfer(client, &entry.tx, to_lamports) {
+                        if let Some(metrics) = metrics {
+                            metrics.record_verified(entry.sent_at.elapsed());
+                        }
+                        Some(entry.keypair.pubkey())
+                    } else {
+                        None
+                    }
+                })
+                .collect();
+            entries.retain(|entry| !verified.contains(&entry.keypair.pubkey()));
 
-            // retry anything that seems to have dropped through cracks
-            //  again since these txs are all or nothing, they're fine to
-            //  retry
-            tries += 1;
+            if !entries.is_empty() {
+                info!("{} left, retrying", entries.len());
+            }
         }
         info!("transferred");
     }
 
-    fn make(&mut self, to_fund: &FundingChunk<'a>) {
+    fn make(&mut self, to_fund: &FundingChunk<'a>, payload_config: &PayloadConfig) {
         let mut make_txs = Measure::start("make_txs");
         let to_fund_txs: FundingContainer<'a> = to_fund
             .par_iter()
-            .map(|(k, t)| {
-                let instructions = system_instruction::transfer_many(&k.pubkey(), t);
+            .enumerate()
+            .map(|(i, (k, t))| {
+                let mut instructions = system_instruction::transfer_many(&k.pubkey(), t);
+                if payload_config.include_memo {
+                    let payload = generate_payload(payload_config, i as u64);
+                    instructions.push(build_memo(&payload, &[]));
+                }
                 let message = Message::new(&instructions, Some(&k.pubkey()));
                 (*k, Transaction::new_unsigned(message))
             })
@@ -318,3 +824,38 @@ impl<'a> FundingTransactions<'a> for FundingContainer<'a> {
         self.extend(to_fund_txs);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histogram_percentile_of_empty_histogram_is_zero() {
+        let histogram = LatencyHistogram::new();
+        assert_eq!(histogram.percentile(0.50), 0);
+    }
+
+    #[test]
+    fn histogram_percentile_crosses_into_the_right_bucket() {
+        let histogram = LatencyHistogram::new();
+        for _ in 0..9 {
+            histogram.record(100); // floor(log2(100)) == 6
+        }
+        histogram.record(10_000); // floor(log2(10_000)) == 13
+        assert_eq!(histogram.percentile(0.50), 1 << 6);
+        assert_eq!(histogram.percentile(0.99), 1 << 13);
+    }
+
+    #[test]
+    fn generate_payload_is_deterministic_per_seed_and_index() {
+        let config = PayloadConfig {
+            include_memo: true,
+            size_bytes: 16,
+            seed: 42,
+        };
+        assert_eq!(generate_payload(&config, 0), generate_payload(&config, 0));
+        assert_ne!(generate_payload(&config, 0), generate_payload(&config, 1));
+        assert_eq!(generate_payload(&config, 0).len(), 16);
+        assert!(std::str::from_utf8(&generate_payload(&config, 0)).is_ok());
+    }
+}
@@ -10,14 +10,17 @@ use {
     smpl_jwt::Jwt,
     std::{
         str::FromStr,
-        sync::{
-            atomic::{AtomicBool, Ordering},
-            {Arc, RwLock},
-        },
-        time::Instant,
+        sync::{Arc, RwLock},
+        time::{Duration, Instant},
     },
+    tokio::task::JoinHandle,
 };
 
+/// Minimum delay between refresh attempts after a failed refresh, so a
+/// persistently unreachable auth endpoint is retried at a bounded rate
+/// instead of being hammered in a busy loop.
+const MIN_REFRESH_RETRY_DELAY: Duration = Duration::from_secs(5);
+
 fn load_credentials(filepath: Option<String>) -> Result<Credentials, String> {
     let path = match filepath {
         Some(f) => f,
@@ -37,7 +40,10 @@ fn load_stringified_credentials(credential: String) -> Result<Credentials, Strin
 pub struct AccessToken {
     credentials: Credentials,
     scope: Scope,
-    refresh_active: Arc<AtomicBool>,
+    // Serializes concurrent refreshes via the async-aware Tokio mutex rather
+    // than a busy-polled atomic flag, so a refresh in progress is waited on
+    // instead of spinning a blocking sleep inside an async fn.
+    refresh_lock: Arc<tokio::sync::Mutex<()>>,
     token: Arc<RwLock<(Token, Instant)>>,
 }
 
@@ -56,7 +62,7 @@ impl AccessToken {
                 credentials,
                 scope,
                 token,
-                refresh_active: Arc::new(AtomicBool::new(false)),
+                refresh_lock: Arc::new(tokio::sync::Mutex::new(())),
             };
             Ok(access_token)
         }
@@ -91,47 +97,58 @@ impl AccessToken {
 
     /// Call this function regularly to ensure the access token does not expire
     pub async fn refresh(&self) {
-        //Check if it's time to try a token refresh
-        {
-            let token_r = self.token.read().unwrap();
+        if !self.needs_refresh() {
+            info!("Token not ready to be refreshed");
+            return;
+        }
 
-            if token_r.1.elapsed().as_secs() < token_r.0.expires_in() as u64 / 2 {
-                info!("Token not ready to be refreshed");
-                return;
-            }
-            warn!("Token ready to be refreshed");
-            warn!("Current Token: {:#?}", self.token);
-
-            #[allow(deprecated)]
-            if self
-                .refresh_active
-                .compare_and_swap(false, true, Ordering::Relaxed)
-            {
-                // Refresh already pending
-                let wait_time: u64 = 2;
-                let wait_time_millis = std::time::Duration::from_millis(wait_time * 1000);
-                warn!("Refresh already pending... waiting {} seconds before trying again...", wait_time);
-
-                thread::sleep(wait_time_millis);
-                self.refresh_active.store(false, Ordering::Relaxed);
-                return;
-            }
+        // Hold the lock for the whole refresh so a second caller that arrives
+        // while a refresh is already in flight just awaits it here instead of
+        // racing to refresh again. No thread is blocked while waiting.
+        let _guard = self.refresh_lock.lock().await;
+        if !self.needs_refresh() {
+            // Another task already refreshed the token while we waited for the lock.
+            return;
         }
 
         warn!("Refreshing token");
-
         let new_token = Self::get_token(&self.credentials, &self.scope).await;
-        {
-            let mut token_w = self.token.write().unwrap();
-            match new_token {
-                Ok(new_token) => *token_w = new_token,
-                Err(err) => warn!("{}", err),
-            }
-            self.refresh_active.store(false, Ordering::Relaxed);
+        match new_token {
+            Ok(new_token) => *self.token.write().unwrap() = new_token,
+            Err(err) => warn!("{}", err),
         }
         warn!("New Token: {:#?}", self.token);
     }
 
+    fn needs_refresh(&self) -> bool {
+        let token_r = self.token.read().unwrap();
+        token_r.1.elapsed().as_secs() >= token_r.0.expires_in() as u64 / 2
+    }
+
+    /// Spawns a background task that keeps this token fresh by refreshing it
+    /// at half its lifetime, so a long-running caller doesn't need to poll
+    /// `refresh` itself to avoid presenting an expired token.
+    pub fn spawn_refresher(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                let sleep_for = {
+                    let token_r = self.token.read().unwrap();
+                    let half_life = Duration::from_secs(token_r.0.expires_in() as u64 / 2);
+                    half_life.saturating_sub(token_r.1.elapsed())
+                };
+                tokio::time::sleep(sleep_for).await;
+                self.refresh().await;
+                if self.needs_refresh() {
+                    // The refresh failed, so the token's `Instant` wasn't
+                    // advanced and `sleep_for` would be computed as ~0 on the
+                    // next iteration. Back off before retrying instead of
+                    // hammering the auth endpoint.
+                    tokio::time::sleep(MIN_REFRESH_RETRY_DELAY).await;
+                }
+            }
+        })
+    }
+
     /// Return an access token suitable for use in an HTTP authorization header
     pub fn get(&self) -> String {
         let token_r = self.token.read().unwrap();